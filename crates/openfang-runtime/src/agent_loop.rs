@@ -14,6 +14,7 @@ use crate::loop_guard::{LoopGuard, LoopGuardConfig, LoopGuardVerdict};
 use crate::mcp::McpConnection;
 use crate::tool_runner;
 use crate::web_search::WebToolsContext;
+use futures::StreamExt;
 use openfang_memory::session::Session;
 use openfang_memory::MemorySubstrate;
 use openfang_skills::registry::SkillRegistry;
@@ -23,7 +24,7 @@ use openfang_types::memory::{Memory, MemoryFilter, MemorySource};
 use openfang_types::message::{
     ContentBlock, Message, MessageContent, Role, StopReason, TokenUsage,
 };
-use openfang_types::tool::{ToolCall, ToolDefinition};
+use openfang_types::tool::{ToolCall, ToolDefinition, ToolResult};
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
@@ -105,10 +106,22 @@ pub struct AgentLoopResult {
     pub directives: openfang_types::message::ReplyDirectives,
 }
 
+/// How the loop core obtains each LLM response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LlmCallMode {
+    /// `LlmDriver::complete` — one response per call, no token events.
+    Complete,
+    /// `LlmDriver::stream` — token events are forwarded to the loop's event channel.
+    Stream,
+}
+
 /// Run the agent execution loop for a single user message.
 ///
 /// This is the core of OpenFang: it loads session context, recalls memories,
 /// runs the LLM in a tool-use loop, and saves the updated session.
+///
+/// Implemented as a collector over the same event-driven core as
+/// `run_agent_loop_streaming`; loop events are drained and discarded.
 #[allow(clippy::too_many_arguments)]
 pub async fn run_agent_loop(
     manifest: &AgentManifest,
@@ -132,7 +145,124 @@ pub async fn run_agent_loop(
     context_window_tokens: Option<usize>,
     process_manager: Option<&crate::process_manager::ProcessManager>,
 ) -> OpenFangResult<AgentLoopResult> {
-    info!(agent = %manifest.name, "Starting agent loop");
+    let (tx, mut rx) = mpsc::channel(64);
+    let drain = async move { while rx.recv().await.is_some() {} };
+    let run = run_agent_loop_core(
+        manifest,
+        user_message,
+        session,
+        memory,
+        driver,
+        available_tools,
+        kernel,
+        tx,
+        skill_registry,
+        mcp_connections,
+        web_ctx,
+        browser_ctx,
+        embedding_driver,
+        workspace_root,
+        on_phase,
+        media_engine,
+        tts_engine,
+        docker_config,
+        hooks,
+        context_window_tokens,
+        process_manager,
+        LlmCallMode::Complete,
+    );
+    let (result, ()) = tokio::join!(run, drain);
+    result
+}
+
+/// Run the agent execution loop with streaming support.
+///
+/// Like `run_agent_loop`, but sends `StreamEvent`s to the provided channel
+/// as tokens arrive from the LLM. Tool execution happens between LLM calls
+/// and is not streamed.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_agent_loop_streaming(
+    manifest: &AgentManifest,
+    user_message: &str,
+    session: &mut Session,
+    memory: &MemorySubstrate,
+    driver: Arc<dyn LlmDriver>,
+    available_tools: &[ToolDefinition],
+    kernel: Option<Arc<dyn KernelHandle>>,
+    stream_tx: mpsc::Sender<StreamEvent>,
+    skill_registry: Option<&SkillRegistry>,
+    mcp_connections: Option<&tokio::sync::Mutex<Vec<McpConnection>>>,
+    web_ctx: Option<&WebToolsContext>,
+    browser_ctx: Option<&crate::browser::BrowserManager>,
+    embedding_driver: Option<&(dyn EmbeddingDriver + Send + Sync)>,
+    workspace_root: Option<&Path>,
+    on_phase: Option<&PhaseCallback>,
+    media_engine: Option<&crate::media_understanding::MediaEngine>,
+    tts_engine: Option<&crate::tts::TtsEngine>,
+    docker_config: Option<&openfang_types::config::DockerSandboxConfig>,
+    hooks: Option<&crate::hooks::HookRegistry>,
+    context_window_tokens: Option<usize>,
+    process_manager: Option<&crate::process_manager::ProcessManager>,
+) -> OpenFangResult<AgentLoopResult> {
+    run_agent_loop_core(
+        manifest,
+        user_message,
+        session,
+        memory,
+        driver,
+        available_tools,
+        kernel,
+        stream_tx,
+        skill_registry,
+        mcp_connections,
+        web_ctx,
+        browser_ctx,
+        embedding_driver,
+        workspace_root,
+        on_phase,
+        media_engine,
+        tts_engine,
+        docker_config,
+        hooks,
+        context_window_tokens,
+        process_manager,
+        LlmCallMode::Stream,
+    )
+    .await
+}
+
+/// Shared agent loop core behind `run_agent_loop` and `run_agent_loop_streaming`.
+///
+/// Emits loop-level `StreamEvent`s (context warnings, tool results) on
+/// `stream_tx`. In `LlmCallMode::Stream` the LLM's own token events are
+/// forwarded there as well; in `LlmCallMode::Complete` the driver is called
+/// without streaming.
+#[allow(clippy::too_many_arguments)]
+async fn run_agent_loop_core(
+    manifest: &AgentManifest,
+    user_message: &str,
+    session: &mut Session,
+    memory: &MemorySubstrate,
+    driver: Arc<dyn LlmDriver>,
+    available_tools: &[ToolDefinition],
+    kernel: Option<Arc<dyn KernelHandle>>,
+    stream_tx: mpsc::Sender<StreamEvent>,
+    skill_registry: Option<&SkillRegistry>,
+    mcp_connections: Option<&tokio::sync::Mutex<Vec<McpConnection>>>,
+    web_ctx: Option<&WebToolsContext>,
+    browser_ctx: Option<&crate::browser::BrowserManager>,
+    embedding_driver: Option<&(dyn EmbeddingDriver + Send + Sync)>,
+    workspace_root: Option<&Path>,
+    on_phase: Option<&PhaseCallback>,
+    media_engine: Option<&crate::media_understanding::MediaEngine>,
+    tts_engine: Option<&crate::tts::TtsEngine>,
+    docker_config: Option<&openfang_types::config::DockerSandboxConfig>,
+    hooks: Option<&crate::hooks::HookRegistry>,
+    context_window_tokens: Option<usize>,
+    process_manager: Option<&crate::process_manager::ProcessManager>,
+    mode: LlmCallMode,
+) -> OpenFangResult<AgentLoopResult> {
+    info!(agent = %manifest.name, streaming = mode == LlmCallMode::Stream, "Starting agent loop");
 
     // Extract hand-allowed env vars from manifest metadata (set by kernel for hand settings)
     let hand_allowed_env: Vec<String> = manifest
@@ -218,8 +348,6 @@ pub async fn run_agent_loop(
     // Add the user message to session history
     session.messages.push(Message::user(user_message));
 
-    // Build the messages for the LLM, filtering system messages
-    // System prompt goes into the separate `system` field
     let llm_messages: Vec<Message> = session
         .messages
         .iter()
@@ -288,8 +416,25 @@ pub async fn run_agent_loop(
         // Context overflow recovery pipeline (replaces emergency_trim_messages)
         let recovery =
             recover_from_overflow(&mut messages, &system_prompt, available_tools, ctx_window);
-        if recovery == RecoveryStage::FinalError {
-            warn!("Context overflow unrecoverable — suggest /reset or /compact");
+        match &recovery {
+            RecoveryStage::None => {}
+            RecoveryStage::FinalError => {
+                warn!("Context overflow unrecoverable — suggest /reset or /compact");
+                if stream_tx.send(StreamEvent::PhaseChange {
+                    phase: "context_warning".to_string(),
+                    detail: Some("Context overflow unrecoverable. Use /reset or /compact.".to_string()),
+                }).await.is_err() {
+                    warn!("Stream consumer disconnected while sending context overflow warning");
+                }
+            }
+            _ => {
+                if stream_tx.send(StreamEvent::PhaseChange {
+                    phase: "context_warning".to_string(),
+                    detail: Some("Older messages trimmed to stay within context limits. Use /compact for smarter summarization.".to_string()),
+                }).await.is_err() {
+                    warn!("Stream consumer disconnected while sending context trim warning");
+                }
+            }
         }
 
         // Context guard: compact oversized tool results before LLM call
@@ -308,14 +453,22 @@ pub async fn run_agent_loop(
             thinking: None,
         };
 
-        // Notify phase: Thinking
+        // Notify phase: Streaming or Thinking, depending on how the LLM is called
         if let Some(cb) = on_phase {
-            cb(LoopPhase::Thinking);
+            cb(match mode {
+                LlmCallMode::Stream => LoopPhase::Streaming,
+                LlmCallMode::Complete => LoopPhase::Thinking,
+            });
         }
 
         // Call LLM with retry, error classification, and circuit breaker
         let provider_name = manifest.model.provider.as_str();
-        let mut response = call_with_retry(&*driver, request, Some(provider_name), None).await?;
+        let token_tx = match mode {
+            LlmCallMode::Stream => Some(&stream_tx),
+            LlmCallMode::Complete => None,
+        };
+        let mut response =
+            call_with_retry(&*driver, request, token_tx, Some(provider_name), None).await?;
 
         total_usage.input_tokens += response.usage.input_tokens;
         total_usage.output_tokens += response.usage.output_tokens;
@@ -350,7 +503,6 @@ pub async fn run_agent_loop(
 
         match response.stop_reason {
             StopReason::EndTurn | StopReason::StopSequence => {
-                // LLM is done — extract text and save
                 let text = response.text();
 
                 // Parse reply directives from the response text
@@ -415,7 +567,6 @@ pub async fn run_agent_loop(
                 // Prune NO_REPLY heartbeat turns to save context budget
                 crate::session_repair::prune_heartbeat_turns(&mut session.messages, 10);
 
-                // Save session
                 memory
                     .save_session(session)
                     .map_err(|e| OpenFangError::Memory(e.to_string()))?;
@@ -521,168 +672,66 @@ pub async fn run_agent_loop(
                 let allowed_tool_names: Vec<String> =
                     available_tools.iter().map(|t| t.name.clone()).collect();
                 let caller_id_str = session.agent_id.to_string();
+                let tool_ctx = ToolTurnContext {
+                    manifest,
+                    kernel: kernel.as_ref(),
+                    allowed_tool_names: &allowed_tool_names,
+                    caller_id: &caller_id_str,
+                    skill_registry,
+                    mcp_connections,
+                    web_ctx,
+                    browser_ctx,
+                    hand_allowed_env: &hand_allowed_env,
+                    workspace_root,
+                    media_engine,
+                    tts_engine,
+                    docker_config,
+                    process_manager,
+                    hooks,
+                    on_phase,
+                    context_budget: &context_budget,
+                };
 
-                // Execute each tool call with loop guard, timeout, and truncation
-                let mut tool_result_blocks = Vec::new();
-                for tool_call in &response.tool_calls {
-                    // Loop guard check
-                    let verdict = loop_guard.check(&tool_call.name, &tool_call.input);
-                    match &verdict {
-                        LoopGuardVerdict::CircuitBreak(msg) => {
-                            warn!(tool = %tool_call.name, "Circuit breaker triggered");
-                            // Save session before bailing
-                            if let Err(e) = memory.save_session(session) {
-                                warn!("Failed to save session on circuit break: {e}");
-                            }
-                            // Fire AgentLoopEnd hook on circuit break
-                            if let Some(hook_reg) = hooks {
-                                let ctx = crate::hooks::HookContext {
-                                    agent_name: &manifest.name,
-                                    agent_id: agent_id_str.as_str(),
-                                    event: openfang_types::agent::HookEvent::AgentLoopEnd,
-                                    data: serde_json::json!({
-                                        "reason": "circuit_break",
-                                        "error": msg.as_str(),
-                                    }),
-                                };
-                                let _ = hook_reg.fire(&ctx);
-                            }
-                            return Err(OpenFangError::Internal(msg.clone()));
-                        }
-                        LoopGuardVerdict::Block(msg) => {
-                            warn!(tool = %tool_call.name, "Tool call blocked by loop guard");
-                            tool_result_blocks.push(ContentBlock::ToolResult {
-                                tool_use_id: tool_call.id.clone(),
-                                content: msg.clone(),
-                                is_error: true,
-                            });
-                            continue;
-                        }
-                        _ => {} // Allow or Warn — proceed with execution
-                    }
-
-                    debug!(tool = %tool_call.name, id = %tool_call.id, "Executing tool");
-
-                    // Notify phase: ToolUse
-                    if let Some(cb) = on_phase {
-                        let sanitized: String = tool_call
-                            .name
-                            .chars()
-                            .filter(|c| !c.is_control())
-                            .take(64)
-                            .collect();
-                        cb(LoopPhase::ToolUse {
-                            tool_name: sanitized,
-                        });
-                    }
-
-                    // Fire BeforeToolCall hook (can block execution)
-                    if let Some(hook_reg) = hooks {
-                        let ctx = crate::hooks::HookContext {
-                            agent_name: &manifest.name,
-                            agent_id: &caller_id_str,
-                            event: openfang_types::agent::HookEvent::BeforeToolCall,
-                            data: serde_json::json!({
-                                "tool_name": &tool_call.name,
-                                "input": &tool_call.input,
-                            }),
-                        };
-                        if let Err(reason) = hook_reg.fire(&ctx) {
-                            tool_result_blocks.push(ContentBlock::ToolResult {
-                                tool_use_id: tool_call.id.clone(),
-                                content: format!(
-                                    "Hook blocked tool '{}': {}",
-                                    tool_call.name, reason
-                                ),
-                                is_error: true,
-                            });
-                            continue;
+                // Execute tool calls with loop guard, timeout, and truncation
+                let mut tool_result_blocks = match execute_tool_calls(
+                    &tool_ctx,
+                    &response.tool_calls,
+                    &mut loop_guard,
+                    Some(&stream_tx),
+                )
+                .await
+                {
+                    Ok(blocks) => blocks,
+                    Err(msg) => {
+                        // Save session before bailing
+                        if let Err(e) = memory.save_session(session) {
+                            warn!("Failed to save session on circuit break: {e}");
                         }
-                    }
-
-                    // Resolve effective exec policy (per-agent override or global)
-                    let effective_exec_policy = manifest.exec_policy.as_ref();
-
-                    // Timeout-wrapped execution
-                    let result = match tokio::time::timeout(
-                        Duration::from_secs(TOOL_TIMEOUT_SECS),
-                        tool_runner::execute_tool(
-                            &tool_call.id,
-                            &tool_call.name,
-                            &tool_call.input,
-                            kernel.as_ref(),
-                            Some(&allowed_tool_names),
-                            Some(&caller_id_str),
-                            skill_registry,
-                            mcp_connections,
-                            web_ctx,
-                            browser_ctx,
-                            if hand_allowed_env.is_empty() {
-                                None
-                            } else {
-                                Some(&hand_allowed_env)
-                            },
-                            workspace_root,
-                            media_engine,
-                            effective_exec_policy,
-                            tts_engine,
-                            docker_config,
-                            process_manager,
-                        ),
-                    )
-                    .await
-                    {
-                        Ok(result) => result,
-                        Err(_) => {
-                            warn!(tool = %tool_call.name, "Tool execution timed out after {}s", TOOL_TIMEOUT_SECS);
-                            openfang_types::tool::ToolResult {
-                                tool_use_id: tool_call.id.clone(),
-                                content: format!(
-                                    "Tool '{}' timed out after {}s.",
-                                    tool_call.name, TOOL_TIMEOUT_SECS
-                                ),
-                                is_error: true,
-                            }
+                        // Fire AgentLoopEnd hook on circuit break
+                        if let Some(hook_reg) = hooks {
+                            let ctx = crate::hooks::HookContext {
+                                agent_name: &manifest.name,
+                                agent_id: agent_id_str.as_str(),
+                                event: openfang_types::agent::HookEvent::AgentLoopEnd,
+                                data: serde_json::json!({
+                                    "reason": "circuit_break",
+                                    "error": msg.as_str(),
+                                }),
+                            };
+                            let _ = hook_reg.fire(&ctx);
                         }
-                    };
-
-                    // Fire AfterToolCall hook
-                    if let Some(hook_reg) = hooks {
-                        let ctx = crate::hooks::HookContext {
-                            agent_name: &manifest.name,
-                            agent_id: caller_id_str.as_str(),
-                            event: openfang_types::agent::HookEvent::AfterToolCall,
-                            data: serde_json::json!({
-                                "tool_name": &tool_call.name,
-                                "result": &result.content,
-                                "is_error": result.is_error,
-                            }),
-                        };
-                        let _ = hook_reg.fire(&ctx);
+                        return Err(OpenFangError::Internal(msg));
                     }
-
-                    // Dynamic truncation based on context budget (replaces flat MAX_TOOL_RESULT_CHARS)
-                    let content = truncate_tool_result_dynamic(&result.content, &context_budget);
-
-                    // Append warning if verdict was Warn
-                    let final_content = if let LoopGuardVerdict::Warn(ref warn_msg) = verdict {
-                        format!("{content}\n\n[LOOP GUARD] {warn_msg}")
-                    } else {
-                        content
-                    };
-
-                    tool_result_blocks.push(ContentBlock::ToolResult {
-                        tool_use_id: result.tool_use_id,
-                        content: final_content,
-                        is_error: result.is_error,
-                    });
-                }
+                };
 
                 // Detect approval denials and inject guidance to prevent infinite retry loops
-                let denial_count = tool_result_blocks.iter().filter(|b| {
-                    matches!(b, ContentBlock::ToolResult { content, is_error: true, .. }
+                let denial_count = tool_result_blocks
+                    .iter()
+                    .filter(|b| {
+                        matches!(b, ContentBlock::ToolResult { content, is_error: true, .. }
                         if content.contains("requires human approval and was denied"))
-                }).count();
+                    })
+                    .count();
                 if denial_count > 0 {
                     tool_result_blocks.push(ContentBlock::Text {
                         text: format!(
@@ -783,11 +832,15 @@ pub async fn run_agent_loop(
 
 /// Call an LLM driver with automatic retry on rate-limit and overload errors.
 ///
+/// When `stream_tx` is set the driver is called in streaming mode and token
+/// events are forwarded to it; otherwise `complete()` is used.
+///
 /// Uses the `llm_errors` classifier for smart error handling and the
 /// `ProviderCooldown` circuit breaker to prevent request storms.
 async fn call_with_retry(
     driver: &dyn LlmDriver,
     request: CompletionRequest,
+    stream_tx: Option<&mpsc::Sender<StreamEvent>>,
     provider: Option<&str>,
     cooldown: Option<&ProviderCooldown>,
 ) -> OpenFangResult<crate::llm_driver::CompletionResponse> {
@@ -812,7 +865,11 @@ async fn call_with_retry(
     let mut last_error = None;
 
     for attempt in 0..=MAX_RETRIES {
-        match driver.complete(request.clone()).await {
+        let result = match stream_tx {
+            Some(tx) => driver.stream(request.clone(), tx.clone()).await,
+            None => driver.complete(request.clone()).await,
+        };
+        match result {
             Ok(response) => {
                 // Record success with circuit breaker
                 if let (Some(provider), Some(cooldown)) = (provider, cooldown) {
@@ -867,833 +924,305 @@ async fn call_with_retry(
                     retryable = classified.is_retryable,
                     raw = %raw_error,
                     "LLM error classified: {}",
-                    classified.sanitized_message
-                );
-
-                if let (Some(provider), Some(cooldown)) = (provider, cooldown) {
-                    cooldown.record_failure(provider, classified.is_billing);
-                }
-
-                // Include raw error detail so dashboard users can debug
-                let user_msg = if classified.category == llm_errors::LlmErrorCategory::Format {
-                    format!("{} — raw: {}", classified.sanitized_message, raw_error)
-                } else {
-                    classified.sanitized_message
-                };
-                return Err(OpenFangError::LlmDriver(user_msg));
-            }
-        }
-    }
-
-    Err(OpenFangError::LlmDriver(
-        last_error.unwrap_or_else(|| "Unknown error".to_string()),
-    ))
-}
-
-/// Call an LLM driver in streaming mode with automatic retry on rate-limit and overload errors.
-///
-/// Uses the `llm_errors` classifier and `ProviderCooldown` circuit breaker.
-async fn stream_with_retry(
-    driver: &dyn LlmDriver,
-    request: CompletionRequest,
-    tx: mpsc::Sender<StreamEvent>,
-    provider: Option<&str>,
-    cooldown: Option<&ProviderCooldown>,
-) -> OpenFangResult<crate::llm_driver::CompletionResponse> {
-    // Check circuit breaker before calling
-    if let (Some(provider), Some(cooldown)) = (provider, cooldown) {
-        match cooldown.check(provider) {
-            CooldownVerdict::Reject {
-                reason,
-                retry_after_secs,
-            } => {
-                return Err(OpenFangError::LlmDriver(format!(
-                    "Provider '{provider}' is in cooldown ({reason}). Retry in {retry_after_secs}s."
-                )));
-            }
-            CooldownVerdict::AllowProbe => {
-                debug!(
-                    provider,
-                    "Allowing probe request through circuit breaker (stream)"
-                );
-            }
-            CooldownVerdict::Allow => {}
-        }
-    }
-
-    let mut last_error = None;
-
-    for attempt in 0..=MAX_RETRIES {
-        match driver.stream(request.clone(), tx.clone()).await {
-            Ok(response) => {
-                if let (Some(provider), Some(cooldown)) = (provider, cooldown) {
-                    cooldown.record_success(provider);
-                }
-                return Ok(response);
-            }
-            Err(LlmError::RateLimited { retry_after_ms }) => {
-                if attempt == MAX_RETRIES {
-                    if let (Some(provider), Some(cooldown)) = (provider, cooldown) {
-                        cooldown.record_failure(provider, false);
-                    }
-                    return Err(OpenFangError::LlmDriver(format!(
-                        "Rate limited after {} retries",
-                        MAX_RETRIES
-                    )));
-                }
-                let delay = std::cmp::max(retry_after_ms, BASE_RETRY_DELAY_MS * 2u64.pow(attempt));
-                warn!(
-                    attempt,
-                    delay_ms = delay,
-                    "Rate limited (stream), retrying after delay"
-                );
-                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
-                last_error = Some("Rate limited".to_string());
-            }
-            Err(LlmError::Overloaded { retry_after_ms }) => {
-                if attempt == MAX_RETRIES {
-                    if let (Some(provider), Some(cooldown)) = (provider, cooldown) {
-                        cooldown.record_failure(provider, false);
-                    }
-                    return Err(OpenFangError::LlmDriver(format!(
-                        "Model overloaded after {} retries",
-                        MAX_RETRIES
-                    )));
-                }
-                let delay = std::cmp::max(retry_after_ms, BASE_RETRY_DELAY_MS * 2u64.pow(attempt));
-                warn!(
-                    attempt,
-                    delay_ms = delay,
-                    "Model overloaded (stream), retrying after delay"
-                );
-                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
-                last_error = Some("Overloaded".to_string());
-            }
-            Err(e) => {
-                let raw_error = e.to_string();
-                let classified = llm_errors::classify_error(&raw_error, None);
-                warn!(
-                    category = ?classified.category,
-                    retryable = classified.is_retryable,
-                    raw = %raw_error,
-                    "LLM stream error classified: {}",
-                    classified.sanitized_message
-                );
-
-                if let (Some(provider), Some(cooldown)) = (provider, cooldown) {
-                    cooldown.record_failure(provider, classified.is_billing);
-                }
-
-                let user_msg = if classified.category == llm_errors::LlmErrorCategory::Format {
-                    format!("{} — raw: {}", classified.sanitized_message, raw_error)
-                } else {
-                    classified.sanitized_message
-                };
-                return Err(OpenFangError::LlmDriver(user_msg));
-            }
-        }
-    }
-
-    Err(OpenFangError::LlmDriver(
-        last_error.unwrap_or_else(|| "Unknown error".to_string()),
-    ))
-}
-
-/// Run the agent execution loop with streaming support.
-///
-/// Like `run_agent_loop`, but sends `StreamEvent`s to the provided channel
-/// as tokens arrive from the LLM. Tool execution happens between LLM calls
-/// and is not streamed.
-#[allow(clippy::too_many_arguments)]
-pub async fn run_agent_loop_streaming(
-    manifest: &AgentManifest,
-    user_message: &str,
-    session: &mut Session,
-    memory: &MemorySubstrate,
-    driver: Arc<dyn LlmDriver>,
-    available_tools: &[ToolDefinition],
-    kernel: Option<Arc<dyn KernelHandle>>,
-    stream_tx: mpsc::Sender<StreamEvent>,
-    skill_registry: Option<&SkillRegistry>,
-    mcp_connections: Option<&tokio::sync::Mutex<Vec<McpConnection>>>,
-    web_ctx: Option<&WebToolsContext>,
-    browser_ctx: Option<&crate::browser::BrowserManager>,
-    embedding_driver: Option<&(dyn EmbeddingDriver + Send + Sync)>,
-    workspace_root: Option<&Path>,
-    on_phase: Option<&PhaseCallback>,
-    media_engine: Option<&crate::media_understanding::MediaEngine>,
-    tts_engine: Option<&crate::tts::TtsEngine>,
-    docker_config: Option<&openfang_types::config::DockerSandboxConfig>,
-    hooks: Option<&crate::hooks::HookRegistry>,
-    context_window_tokens: Option<usize>,
-    process_manager: Option<&crate::process_manager::ProcessManager>,
-) -> OpenFangResult<AgentLoopResult> {
-    info!(agent = %manifest.name, "Starting streaming agent loop");
-
-    // Extract hand-allowed env vars from manifest metadata (set by kernel for hand settings)
-    let hand_allowed_env: Vec<String> = manifest
-        .metadata
-        .get("hand_allowed_env")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
-
-    // Recall relevant memories — prefer vector similarity search when embedding driver is available
-    let memories = if let Some(emb) = embedding_driver {
-        match emb.embed_one(user_message).await {
-            Ok(query_vec) => {
-                debug!("Using vector recall (streaming, dims={})", query_vec.len());
-                memory
-                    .recall_with_embedding_async(
-                        user_message,
-                        5,
-                        Some(MemoryFilter {
-                            agent_id: Some(session.agent_id),
-                            ..Default::default()
-                        }),
-                        Some(&query_vec),
-                    )
-                    .await
-                    .unwrap_or_default()
-            }
-            Err(e) => {
-                warn!("Embedding recall failed (streaming), falling back to text search: {e}");
-                memory
-                    .recall(
-                        user_message,
-                        5,
-                        Some(MemoryFilter {
-                            agent_id: Some(session.agent_id),
-                            ..Default::default()
-                        }),
-                    )
-                    .await
-                    .unwrap_or_default()
-            }
-        }
-    } else {
-        memory
-            .recall(
-                user_message,
-                5,
-                Some(MemoryFilter {
-                    agent_id: Some(session.agent_id),
-                    ..Default::default()
-                }),
-            )
-            .await
-            .unwrap_or_default()
-    };
-
-    // Fire BeforePromptBuild hook
-    let agent_id_str = session.agent_id.0.to_string();
-    if let Some(hook_reg) = hooks {
-        let ctx = crate::hooks::HookContext {
-            agent_name: &manifest.name,
-            agent_id: agent_id_str.as_str(),
-            event: openfang_types::agent::HookEvent::BeforePromptBuild,
-            data: serde_json::json!({
-                "system_prompt": &manifest.model.system_prompt,
-                "user_message": user_message,
-            }),
-        };
-        let _ = hook_reg.fire(&ctx);
-    }
-
-    // Build the system prompt — base prompt comes from kernel (prompt_builder),
-    // we append recalled memories here since they are resolved at loop time.
-    let mut system_prompt = manifest.model.system_prompt.clone();
-    if !memories.is_empty() {
-        let mem_pairs: Vec<(String, String)> = memories
-            .iter()
-            .map(|m| (String::new(), m.content.clone()))
-            .collect();
-        system_prompt.push_str("\n\n");
-        system_prompt.push_str(&crate::prompt_builder::build_memory_section(&mem_pairs));
-    }
-
-    // Add the user message to session history
-    session.messages.push(Message::user(user_message));
-
-    let llm_messages: Vec<Message> = session
-        .messages
-        .iter()
-        .filter(|m| m.role != Role::System)
-        .cloned()
-        .collect();
-
-    // Validate and repair session history (drop orphans, merge consecutive)
-    let mut messages = crate::session_repair::validate_and_repair(&llm_messages);
-
-    // Inject canonical context as the first user message (not in system prompt)
-    // to keep the system prompt stable across turns for provider prompt caching.
-    if let Some(cc_msg) = manifest
-        .metadata
-        .get("canonical_context_msg")
-        .and_then(|v| v.as_str())
-    {
-        if !cc_msg.is_empty() {
-            messages.insert(0, Message::user(cc_msg));
-        }
-    }
-
-    let mut total_usage = TokenUsage::default();
-    let final_response;
-
-    // Safety valve: trim excessively long message histories to prevent context overflow.
-    if messages.len() > MAX_HISTORY_MESSAGES {
-        let trim_count = messages.len() - MAX_HISTORY_MESSAGES;
-        warn!(
-            agent = %manifest.name,
-            total_messages = messages.len(),
-            trimming = trim_count,
-            "Trimming old messages to prevent context overflow (streaming)"
-        );
-        messages.drain(..trim_count);
-    }
-
-    // Use autonomous config max_iterations if set, else default
-    let max_iterations = manifest
-        .autonomous
-        .as_ref()
-        .map(|a| a.max_iterations)
-        .unwrap_or(MAX_ITERATIONS);
-
-    // Initialize loop guard — scale circuit breaker for autonomous agents
-    let loop_guard_config = {
-        let mut cfg = LoopGuardConfig::default();
-        if max_iterations > cfg.global_circuit_breaker {
-            cfg.global_circuit_breaker = max_iterations * 3;
-        }
-        cfg
-    };
-    let mut loop_guard = LoopGuard::new(loop_guard_config);
-    let mut consecutive_max_tokens: u32 = 0;
-
-    // Build context budget from model's actual context window (or fallback to default)
-    let ctx_window = context_window_tokens.unwrap_or(DEFAULT_CONTEXT_WINDOW);
-    let context_budget = ContextBudget::new(ctx_window);
-    let mut any_tools_executed = false;
-
-    for iteration in 0..max_iterations {
-        debug!(iteration, "Streaming agent loop iteration");
-
-        // Context overflow recovery pipeline (replaces emergency_trim_messages)
-        let recovery =
-            recover_from_overflow(&mut messages, &system_prompt, available_tools, ctx_window);
-        match &recovery {
-            RecoveryStage::None => {}
-            RecoveryStage::FinalError => {
-                if stream_tx.send(StreamEvent::PhaseChange {
-                    phase: "context_warning".to_string(),
-                    detail: Some("Context overflow unrecoverable. Use /reset or /compact.".to_string()),
-                }).await.is_err() {
-                    warn!("Stream consumer disconnected while sending context overflow warning");
-                }
-            }
-            _ => {
-                if stream_tx.send(StreamEvent::PhaseChange {
-                    phase: "context_warning".to_string(),
-                    detail: Some("Older messages trimmed to stay within context limits. Use /compact for smarter summarization.".to_string()),
-                }).await.is_err() {
-                    warn!("Stream consumer disconnected while sending context trim warning");
-                }
-            }
-        }
-
-        // Context guard: compact oversized tool results before LLM call
-        apply_context_guard(&mut messages, &context_budget, available_tools);
-
-        // Strip provider prefix: "openrouter/google/gemini-2.5-flash" → "google/gemini-2.5-flash"
-        let api_model = strip_provider_prefix(&manifest.model.model, &manifest.model.provider);
-
-        let request = CompletionRequest {
-            model: api_model,
-            messages: messages.clone(),
-            tools: available_tools.to_vec(),
-            max_tokens: manifest.model.max_tokens,
-            temperature: manifest.model.temperature,
-            system: Some(system_prompt.clone()),
-            thinking: None,
-        };
-
-        // Notify phase: Streaming (streaming variant always streams)
-        if let Some(cb) = on_phase {
-            cb(LoopPhase::Streaming);
-        }
-
-        // Stream LLM call with retry, error classification, and circuit breaker
-        let provider_name = manifest.model.provider.as_str();
-        let mut response = stream_with_retry(
-            &*driver,
-            request,
-            stream_tx.clone(),
-            Some(provider_name),
-            None,
-        )
-        .await?;
-
-        total_usage.input_tokens += response.usage.input_tokens;
-        total_usage.output_tokens += response.usage.output_tokens;
-
-        // Recover tool calls output as text (streaming path)
-        if matches!(
-            response.stop_reason,
-            StopReason::EndTurn | StopReason::StopSequence
-        ) && response.tool_calls.is_empty()
-        {
-            let recovered = recover_text_tool_calls(&response.text(), available_tools);
-            if !recovered.is_empty() {
-                info!(
-                    count = recovered.len(),
-                    "Recovered text-based tool calls (streaming) → promoting to ToolUse"
-                );
-                response.tool_calls = recovered;
-                response.stop_reason = StopReason::ToolUse;
-                let mut new_blocks: Vec<ContentBlock> = Vec::new();
-                for tc in &response.tool_calls {
-                    new_blocks.push(ContentBlock::ToolUse {
-                        id: tc.id.clone(),
-                        name: tc.name.clone(),
-                        input: tc.input.clone(),
-                    });
-                }
-                response.content = new_blocks;
-            }
-        }
-
-        match response.stop_reason {
-            StopReason::EndTurn | StopReason::StopSequence => {
-                let text = response.text();
-
-                // Parse reply directives from the streaming response text
-                let (cleaned_text_s, parsed_directives_s) =
-                    crate::reply_directives::parse_directives(&text);
-                let text = cleaned_text_s;
-
-                // NO_REPLY: agent intentionally chose not to reply
-                if text.trim() == "NO_REPLY" || parsed_directives_s.silent {
-                    debug!(agent = %manifest.name, "Agent chose NO_REPLY/silent (streaming) — silent completion");
-                    session
-                        .messages
-                        .push(Message::assistant("[no reply needed]".to_string()));
-                    memory
-                        .save_session(session)
-                        .map_err(|e| OpenFangError::Memory(e.to_string()))?;
-                    return Ok(AgentLoopResult {
-                        response: String::new(),
-                        total_usage,
-                        iterations: iteration + 1,
-                        cost_usd: None,
-                        silent: true,
-                        directives: openfang_types::message::ReplyDirectives {
-                            reply_to: parsed_directives_s.reply_to,
-                            current_thread: parsed_directives_s.current_thread,
-                            silent: true,
-                        },
-                    });
-                }
-
-                // One-shot retry: if the very first LLM call returns empty text
-                // with no tool use, try once more before accepting the empty result.
-                if text.trim().is_empty() && iteration == 0 && response.tool_calls.is_empty() {
-                    warn!(agent = %manifest.name, "Empty response on first call (streaming), retrying once");
-                    messages.push(Message::assistant("[no response]".to_string()));
-                    messages.push(Message::user("Please provide your response.".to_string()));
-                    continue;
-                }
-
-                // Guard against empty response — covers both iteration 0 and post-tool cycles
-                let text = if text.trim().is_empty() {
-                    warn!(
-                        agent = %manifest.name,
-                        iteration,
-                        input_tokens = total_usage.input_tokens,
-                        output_tokens = total_usage.output_tokens,
-                        messages_count = messages.len(),
-                        "Empty response from LLM (streaming) — guard activated"
-                    );
-                    if any_tools_executed {
-                        "[Task completed — the agent executed tools but did not produce a text summary.]".to_string()
-                    } else {
-                        "[The model returned an empty response. This usually means the model is overloaded, the context is too large, or the API key lacks credits. Try again or check /status.]".to_string()
-                    }
-                } else {
-                    text
-                };
-                final_response = text.clone();
-                session.messages.push(Message::assistant(text));
-
-                // Prune NO_REPLY heartbeat turns to save context budget
-                crate::session_repair::prune_heartbeat_turns(&mut session.messages, 10);
-
-                memory
-                    .save_session(session)
-                    .map_err(|e| OpenFangError::Memory(e.to_string()))?;
-
-                // Remember this interaction (with embedding if available)
-                let interaction_text = format!(
-                    "User asked: {}\nI responded: {}",
-                    user_message, final_response
-                );
-                if let Some(emb) = embedding_driver {
-                    match emb.embed_one(&interaction_text).await {
-                        Ok(vec) => {
-                            let _ = memory
-                                .remember_with_embedding_async(
-                                    session.agent_id,
-                                    &interaction_text,
-                                    MemorySource::Conversation,
-                                    "episodic",
-                                    HashMap::new(),
-                                    Some(&vec),
-                                )
-                                .await;
-                        }
-                        Err(e) => {
-                            warn!("Embedding for remember failed (streaming): {e}");
-                            let _ = memory
-                                .remember(
-                                    session.agent_id,
-                                    &interaction_text,
-                                    MemorySource::Conversation,
-                                    "episodic",
-                                    HashMap::new(),
-                                )
-                                .await;
-                        }
-                    }
-                } else {
-                    let _ = memory
-                        .remember(
-                            session.agent_id,
-                            &interaction_text,
-                            MemorySource::Conversation,
-                            "episodic",
-                            HashMap::new(),
-                        )
-                        .await;
-                }
-
-                // Notify phase: Done
-                if let Some(cb) = on_phase {
-                    cb(LoopPhase::Done);
-                }
-
-                info!(
-                    agent = %manifest.name,
-                    iterations = iteration + 1,
-                    tokens = total_usage.total(),
-                    "Streaming agent loop completed"
-                );
-
-                // Fire AgentLoopEnd hook
-                if let Some(hook_reg) = hooks {
-                    let ctx = crate::hooks::HookContext {
-                        agent_name: &manifest.name,
-                        agent_id: agent_id_str.as_str(),
-                        event: openfang_types::agent::HookEvent::AgentLoopEnd,
-                        data: serde_json::json!({
-                            "iterations": iteration + 1,
-                            "response_length": final_response.len(),
-                        }),
-                    };
-                    let _ = hook_reg.fire(&ctx);
-                }
-
-                return Ok(AgentLoopResult {
-                    response: final_response,
-                    total_usage,
-                    iterations: iteration + 1,
-                    cost_usd: None,
-                    silent: false,
-                    directives: Default::default(),
-                });
-            }
-            StopReason::ToolUse => {
-                // Reset MaxTokens continuation counter on tool use
-                consecutive_max_tokens = 0;
-                any_tools_executed = true;
-
-                let assistant_blocks = response.content.clone();
-
-                session.messages.push(Message {
-                    role: Role::Assistant,
-                    content: MessageContent::Blocks(assistant_blocks.clone()),
-                });
-                messages.push(Message {
-                    role: Role::Assistant,
-                    content: MessageContent::Blocks(assistant_blocks),
-                });
-
-                let allowed_tool_names: Vec<String> =
-                    available_tools.iter().map(|t| t.name.clone()).collect();
-                let caller_id_str = session.agent_id.to_string();
-
-                // Execute each tool call with loop guard, timeout, and truncation
-                let mut tool_result_blocks = Vec::new();
-                for tool_call in &response.tool_calls {
-                    // Loop guard check
-                    let verdict = loop_guard.check(&tool_call.name, &tool_call.input);
-                    match &verdict {
-                        LoopGuardVerdict::CircuitBreak(msg) => {
-                            warn!(tool = %tool_call.name, "Circuit breaker triggered (streaming)");
-                            if let Err(e) = memory.save_session(session) {
-                                warn!("Failed to save session on circuit break: {e}");
-                            }
-                            // Fire AgentLoopEnd hook on circuit break
-                            if let Some(hook_reg) = hooks {
-                                let ctx = crate::hooks::HookContext {
-                                    agent_name: &manifest.name,
-                                    agent_id: agent_id_str.as_str(),
-                                    event: openfang_types::agent::HookEvent::AgentLoopEnd,
-                                    data: serde_json::json!({
-                                        "reason": "circuit_break",
-                                        "error": msg.as_str(),
-                                    }),
-                                };
-                                let _ = hook_reg.fire(&ctx);
-                            }
-                            return Err(OpenFangError::Internal(msg.clone()));
-                        }
-                        LoopGuardVerdict::Block(msg) => {
-                            warn!(tool = %tool_call.name, "Tool call blocked by loop guard (streaming)");
-                            tool_result_blocks.push(ContentBlock::ToolResult {
-                                tool_use_id: tool_call.id.clone(),
-                                content: msg.clone(),
-                                is_error: true,
-                            });
-                            continue;
-                        }
-                        _ => {} // Allow or Warn — proceed with execution
-                    }
-
-                    debug!(tool = %tool_call.name, id = %tool_call.id, "Executing tool (streaming)");
-
-                    // Notify phase: ToolUse
-                    if let Some(cb) = on_phase {
-                        let sanitized: String = tool_call
-                            .name
-                            .chars()
-                            .filter(|c| !c.is_control())
-                            .take(64)
-                            .collect();
-                        cb(LoopPhase::ToolUse {
-                            tool_name: sanitized,
-                        });
-                    }
-
-                    // Fire BeforeToolCall hook (can block execution)
-                    if let Some(hook_reg) = hooks {
-                        let ctx = crate::hooks::HookContext {
-                            agent_name: &manifest.name,
-                            agent_id: &caller_id_str,
-                            event: openfang_types::agent::HookEvent::BeforeToolCall,
-                            data: serde_json::json!({
-                                "tool_name": &tool_call.name,
-                                "input": &tool_call.input,
-                            }),
-                        };
-                        if let Err(reason) = hook_reg.fire(&ctx) {
-                            tool_result_blocks.push(ContentBlock::ToolResult {
-                                tool_use_id: tool_call.id.clone(),
-                                content: format!(
-                                    "Hook blocked tool '{}': {}",
-                                    tool_call.name, reason
-                                ),
-                                is_error: true,
-                            });
-                            continue;
-                        }
-                    }
-
-                    // Resolve effective exec policy (per-agent override or global)
-                    let effective_exec_policy = manifest.exec_policy.as_ref();
-
-                    // Timeout-wrapped execution
-                    let result = match tokio::time::timeout(
-                        Duration::from_secs(TOOL_TIMEOUT_SECS),
-                        tool_runner::execute_tool(
-                            &tool_call.id,
-                            &tool_call.name,
-                            &tool_call.input,
-                            kernel.as_ref(),
-                            Some(&allowed_tool_names),
-                            Some(&caller_id_str),
-                            skill_registry,
-                            mcp_connections,
-                            web_ctx,
-                            browser_ctx,
-                            if hand_allowed_env.is_empty() {
-                                None
-                            } else {
-                                Some(&hand_allowed_env)
-                            },
-                            workspace_root,
-                            media_engine,
-                            effective_exec_policy,
-                            tts_engine,
-                            docker_config,
-                            process_manager,
-                        ),
-                    )
-                    .await
-                    {
-                        Ok(result) => result,
-                        Err(_) => {
-                            warn!(tool = %tool_call.name, "Tool execution timed out after {}s (streaming)", TOOL_TIMEOUT_SECS);
-                            openfang_types::tool::ToolResult {
-                                tool_use_id: tool_call.id.clone(),
-                                content: format!(
-                                    "Tool '{}' timed out after {}s.",
-                                    tool_call.name, TOOL_TIMEOUT_SECS
-                                ),
-                                is_error: true,
-                            }
-                        }
-                    };
-
-                    // Fire AfterToolCall hook
-                    if let Some(hook_reg) = hooks {
-                        let ctx = crate::hooks::HookContext {
-                            agent_name: &manifest.name,
-                            agent_id: caller_id_str.as_str(),
-                            event: openfang_types::agent::HookEvent::AfterToolCall,
-                            data: serde_json::json!({
-                                "tool_name": &tool_call.name,
-                                "result": &result.content,
-                                "is_error": result.is_error,
-                            }),
-                        };
-                        let _ = hook_reg.fire(&ctx);
-                    }
+                    classified.sanitized_message
+                );
 
-                    // Dynamic truncation based on context budget (replaces flat MAX_TOOL_RESULT_CHARS)
-                    let content = truncate_tool_result_dynamic(&result.content, &context_budget);
+                if let (Some(provider), Some(cooldown)) = (provider, cooldown) {
+                    cooldown.record_failure(provider, classified.is_billing);
+                }
 
-                    // Append warning if verdict was Warn
-                    let final_content = if let LoopGuardVerdict::Warn(ref warn_msg) = verdict {
-                        format!("{content}\n\n[LOOP GUARD] {warn_msg}")
-                    } else {
-                        content
-                    };
+                // Include raw error detail so dashboard users can debug
+                let user_msg = if classified.category == llm_errors::LlmErrorCategory::Format {
+                    format!("{} — raw: {}", classified.sanitized_message, raw_error)
+                } else {
+                    classified.sanitized_message
+                };
+                return Err(OpenFangError::LlmDriver(user_msg));
+            }
+        }
+    }
 
-                    // Notify client of tool execution result (detect dead consumer)
-                    let preview: String = final_content.chars().take(300).collect();
-                    if stream_tx
-                        .send(StreamEvent::ToolExecutionResult {
-                            name: tool_call.name.clone(),
-                            result_preview: preview,
-                            is_error: result.is_error,
-                        })
-                        .await
-                        .is_err()
-                    {
-                        warn!(agent = %manifest.name, "Stream consumer disconnected — continuing tool loop but will not stream further");
-                    }
+    Err(OpenFangError::LlmDriver(
+        last_error.unwrap_or_else(|| "Unknown error".to_string()),
+    ))
+}
 
-                    tool_result_blocks.push(ContentBlock::ToolResult {
-                        tool_use_id: result.tool_use_id,
-                        content: final_content,
-                        is_error: result.is_error,
-                    });
-                }
+struct ToolTurnContext<'a> {
+    manifest: &'a AgentManifest,
+    kernel: Option<&'a Arc<dyn KernelHandle>>,
+    allowed_tool_names: &'a [String],
+    caller_id: &'a str,
+    skill_registry: Option<&'a SkillRegistry>,
+    mcp_connections: Option<&'a tokio::sync::Mutex<Vec<McpConnection>>>,
+    web_ctx: Option<&'a WebToolsContext>,
+    browser_ctx: Option<&'a crate::browser::BrowserManager>,
+    hand_allowed_env: &'a [String],
+    workspace_root: Option<&'a Path>,
+    media_engine: Option<&'a crate::media_understanding::MediaEngine>,
+    tts_engine: Option<&'a crate::tts::TtsEngine>,
+    docker_config: Option<&'a openfang_types::config::DockerSandboxConfig>,
+    process_manager: Option<&'a crate::process_manager::ProcessManager>,
+    hooks: Option<&'a crate::hooks::HookRegistry>,
+    on_phase: Option<&'a PhaseCallback>,
+    context_budget: &'a ContextBudget,
+}
 
-                // Detect approval denials and inject guidance to prevent infinite retry loops
-                let denial_count = tool_result_blocks.iter().filter(|b| {
-                    matches!(b, ContentBlock::ToolResult { content, is_error: true, .. }
-                        if content.contains("requires human approval and was denied"))
-                }).count();
-                if denial_count > 0 {
-                    tool_result_blocks.push(ContentBlock::Text {
-                        text: format!(
-                            "[System: {} tool call(s) were denied by approval policy. \
-                             Do NOT retry denied tools. Explain to the user what you \
-                             wanted to do and that it requires their approval.]",
-                            denial_count
-                        ),
-                    });
+impl ToolTurnContext<'_> {
+    /// Whether a tool may run concurrently with other parallel-safe calls.
+    ///
+    /// Tools gated by human approval always run alone so prompts arrive one at a time.
+    fn is_parallel_safe(&self, tool_name: &str) -> bool {
+        if self.kernel.is_some_and(|k| k.requires_approval(tool_name)) {
+            return false;
+        }
+        tool_runner::is_parallel_safe_tool(tool_name)
+            || self
+                .manifest
+                .tools
+                .get(tool_name)
+                .is_some_and(|c| c.parallel_safe)
+    }
+
+    /// Execute a single tool call with the per-tool timeout.
+    async fn execute(&self, tool_call: &ToolCall) -> ToolResult {
+        debug!(tool = %tool_call.name, id = %tool_call.id, "Executing tool");
+
+        // Resolve effective exec policy (per-agent override or global)
+        let effective_exec_policy = self.manifest.exec_policy.as_ref();
+
+        match tokio::time::timeout(
+            Duration::from_secs(TOOL_TIMEOUT_SECS),
+            tool_runner::execute_tool(
+                &tool_call.id,
+                &tool_call.name,
+                &tool_call.input,
+                self.kernel,
+                Some(self.allowed_tool_names),
+                Some(self.caller_id),
+                self.skill_registry,
+                self.mcp_connections,
+                self.web_ctx,
+                self.browser_ctx,
+                if self.hand_allowed_env.is_empty() {
+                    None
+                } else {
+                    Some(self.hand_allowed_env)
+                },
+                self.workspace_root,
+                self.media_engine,
+                effective_exec_policy,
+                self.tts_engine,
+                self.docker_config,
+                self.process_manager,
+            ),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => {
+                warn!(tool = %tool_call.name, "Tool execution timed out after {}s", TOOL_TIMEOUT_SECS);
+                ToolResult {
+                    tool_use_id: tool_call.id.clone(),
+                    content: format!(
+                        "Tool '{}' timed out after {}s.",
+                        tool_call.name, TOOL_TIMEOUT_SECS
+                    ),
+                    is_error: true,
                 }
+            }
+        }
+    }
 
-                let tool_results_msg = Message {
-                    role: Role::User,
-                    content: MessageContent::Blocks(tool_result_blocks.clone()),
-                };
-                session.messages.push(tool_results_msg.clone());
-                messages.push(tool_results_msg);
+    /// Post-process a tool result: AfterToolCall hook, truncation, loop guard
+    /// warning, and (when streaming) a `ToolExecutionResult` event.
+    async fn finish(
+        &self,
+        tool_call: &ToolCall,
+        result: ToolResult,
+        verdict: &LoopGuardVerdict,
+        stream_tx: Option<&mpsc::Sender<StreamEvent>>,
+    ) -> ContentBlock {
+        // Fire AfterToolCall hook
+        if let Some(hook_reg) = self.hooks {
+            let ctx = crate::hooks::HookContext {
+                agent_name: &self.manifest.name,
+                agent_id: self.caller_id,
+                event: openfang_types::agent::HookEvent::AfterToolCall,
+                data: serde_json::json!({
+                    "tool_name": &tool_call.name,
+                    "result": &result.content,
+                    "is_error": result.is_error,
+                }),
+            };
+            let _ = hook_reg.fire(&ctx);
+        }
 
-                if let Err(e) = memory.save_session(session) {
-                    warn!("Failed to interim-save session: {e}");
-                }
+        // Dynamic truncation based on context budget (replaces flat MAX_TOOL_RESULT_CHARS)
+        let content = truncate_tool_result_dynamic(&result.content, self.context_budget);
+
+        // Append warning if verdict was Warn
+        let final_content = if let LoopGuardVerdict::Warn(warn_msg) = verdict {
+            format!("{content}\n\n[LOOP GUARD] {warn_msg}")
+        } else {
+            content
+        };
+
+        // Notify client of tool execution result (detect dead consumer)
+        if let Some(tx) = stream_tx {
+            let preview: String = final_content.chars().take(300).collect();
+            if tx
+                .send(StreamEvent::ToolExecutionResult {
+                    name: tool_call.name.clone(),
+                    result_preview: preview,
+                    is_error: result.is_error,
+                })
+                .await
+                .is_err()
+            {
+                warn!(agent = %self.manifest.name, "Stream consumer disconnected — continuing tool loop but will not stream further");
             }
-            StopReason::MaxTokens => {
-                consecutive_max_tokens += 1;
-                if consecutive_max_tokens >= MAX_CONTINUATIONS {
-                    let text = response.text();
-                    let text = if text.trim().is_empty() {
-                        "[Partial response — token limit reached with no text output.]".to_string()
-                    } else {
-                        text
-                    };
-                    session.messages.push(Message::assistant(&text));
-                    if let Err(e) = memory.save_session(session) {
-                        warn!("Failed to save session on max continuations: {e}");
-                    }
-                    warn!(
-                        iteration,
-                        consecutive_max_tokens,
-                        "Max continuations reached (streaming), returning partial response"
-                    );
-                    // Fire AgentLoopEnd hook
-                    if let Some(hook_reg) = hooks {
-                        let ctx = crate::hooks::HookContext {
-                            agent_name: &manifest.name,
-                            agent_id: agent_id_str.as_str(),
-                            event: openfang_types::agent::HookEvent::AgentLoopEnd,
-                            data: serde_json::json!({
-                                "iterations": iteration + 1,
-                                "reason": "max_continuations",
-                            }),
-                        };
-                        let _ = hook_reg.fire(&ctx);
-                    }
-                    return Ok(AgentLoopResult {
-                        response: text,
-                        total_usage,
-                        iterations: iteration + 1,
-                        cost_usd: None,
-                        silent: false,
-                        directives: Default::default(),
-                    });
-                }
-                let text = response.text();
-                session.messages.push(Message::assistant(&text));
-                messages.push(Message::assistant(&text));
-                session.messages.push(Message::user("Please continue."));
-                messages.push(Message::user("Please continue."));
-                warn!(iteration, "Max tokens hit (streaming), continuing");
+        }
+
+        ContentBlock::ToolResult {
+            tool_use_id: result.tool_use_id,
+            content: final_content,
+            is_error: result.is_error,
+        }
+    }
+}
+
+/// Split pending tool calls into execution runs.
+///
+/// Each maximal stretch of consecutive parallel-safe calls becomes one run;
+/// every other call is a run of its own. Runs execute in order, so a call with
+/// side effects never overlaps with the calls before or after it.
+fn parallel_runs(parallel_safe: &[bool]) -> Vec<std::ops::Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < parallel_safe.len() {
+        let mut end = start + 1;
+        if parallel_safe[start] {
+            while end < parallel_safe.len() && parallel_safe[end] {
+                end += 1;
             }
         }
+        runs.push(start..end);
+        start = end;
     }
+    runs
+}
 
-    if let Err(e) = memory.save_session(session) {
-        warn!("Failed to save session on max iterations: {e}");
+/// Execute the tool calls from one assistant message.
+///
+/// Loop guard checks and `BeforeToolCall` hooks run sequentially in request
+/// order. Consecutive parallel-safe calls are then executed concurrently, up to
+/// the agent's `resources.max_parallel_tools`. Results are returned in the
+/// order the LLM requested them. Returns `Err` with the loop guard message if
+/// the circuit breaker trips.
+async fn execute_tool_calls(
+    ctx: &ToolTurnContext<'_>,
+    tool_calls: &[ToolCall],
+    loop_guard: &mut LoopGuard,
+    stream_tx: Option<&mpsc::Sender<StreamEvent>>,
+) -> Result<Vec<ContentBlock>, String> {
+    let mut slots: Vec<Option<ContentBlock>> = vec![None; tool_calls.len()];
+    let mut pending: Vec<(usize, LoopGuardVerdict)> = Vec::new();
+
+    for (idx, tool_call) in tool_calls.iter().enumerate() {
+        // Loop guard check
+        let verdict = loop_guard.check(&tool_call.name, &tool_call.input);
+        match &verdict {
+            LoopGuardVerdict::CircuitBreak(msg) => {
+                warn!(tool = %tool_call.name, "Circuit breaker triggered");
+                return Err(msg.clone());
+            }
+            LoopGuardVerdict::Block(msg) => {
+                warn!(tool = %tool_call.name, "Tool call blocked by loop guard");
+                slots[idx] = Some(ContentBlock::ToolResult {
+                    tool_use_id: tool_call.id.clone(),
+                    content: msg.clone(),
+                    is_error: true,
+                });
+                continue;
+            }
+            _ => {} // Allow or Warn — proceed with execution
+        }
+
+        // Fire BeforeToolCall hook (can block execution)
+        if let Some(hook_reg) = ctx.hooks {
+            let hook_ctx = crate::hooks::HookContext {
+                agent_name: &ctx.manifest.name,
+                agent_id: ctx.caller_id,
+                event: openfang_types::agent::HookEvent::BeforeToolCall,
+                data: serde_json::json!({
+                    "tool_name": &tool_call.name,
+                    "input": &tool_call.input,
+                }),
+            };
+            if let Err(reason) = hook_reg.fire(&hook_ctx) {
+                slots[idx] = Some(ContentBlock::ToolResult {
+                    tool_use_id: tool_call.id.clone(),
+                    content: format!("Hook blocked tool '{}': {}", tool_call.name, reason),
+                    is_error: true,
+                });
+                continue;
+            }
+        }
+
+        pending.push((idx, verdict));
     }
 
-    // Fire AgentLoopEnd hook on max iterations exceeded
-    if let Some(hook_reg) = hooks {
-        let ctx = crate::hooks::HookContext {
-            agent_name: &manifest.name,
-            agent_id: agent_id_str.as_str(),
-            event: openfang_types::agent::HookEvent::AgentLoopEnd,
-            data: serde_json::json!({
-                "reason": "max_iterations_exceeded",
-                "iterations": max_iterations,
-            }),
-        };
-        let _ = hook_reg.fire(&ctx);
+    let max_parallel = ctx.manifest.resources.max_parallel_tools.max(1) as usize;
+    let parallel_safe: Vec<bool> = pending
+        .iter()
+        .map(|(idx, _)| max_parallel > 1 && ctx.is_parallel_safe(&tool_calls[*idx].name))
+        .collect();
+
+    for run in parallel_runs(&parallel_safe) {
+        let batch = &pending[run];
+        if batch.len() > 1 {
+            debug!(
+                count = batch.len(),
+                max_parallel, "Executing tool calls in parallel"
+            );
+        }
+
+        // Notify phase: ToolUse
+        if let Some(cb) = ctx.on_phase {
+            for (idx, _) in batch {
+                let sanitized: String = tool_calls[*idx]
+                    .name
+                    .chars()
+                    .filter(|c| !c.is_control())
+                    .take(64)
+                    .collect();
+                cb(LoopPhase::ToolUse {
+                    tool_name: sanitized,
+                });
+            }
+        }
+
+        // Futures are collected up front (rather than mapped lazily) so the
+        // spawned agent task stays `Send`. `buffered` yields results in input
+        // order regardless of completion order.
+        let mut futs = Vec::with_capacity(batch.len());
+        for (idx, _) in batch {
+            futs.push(ctx.execute(&tool_calls[*idx]));
+        }
+        let results: Vec<ToolResult> = futures::stream::iter(futs)
+            .buffered(max_parallel)
+            .collect()
+            .await;
+
+        for ((idx, verdict), result) in batch.iter().zip(results) {
+            slots[*idx] = Some(
+                ctx.finish(&tool_calls[*idx], result, verdict, stream_tx)
+                    .await,
+            );
+        }
     }
 
-    Err(OpenFangError::MaxIterationsExceeded(max_iterations))
+    Ok(slots.into_iter().flatten().collect())
 }
 
 /// Recover tool calls that LLMs (Groq/Llama, DeepSeek) output as plain text
@@ -2858,6 +2387,256 @@ mod tests {
         );
     }
 
+    // --- Shared loop core: both entry points must behave identically ---
+
+    /// Run one scenario through `run_agent_loop` and `run_agent_loop_streaming`,
+    /// returning the results and the phases each reported.
+    async fn run_both_modes(
+        make_driver: impl Fn() -> Arc<dyn LlmDriver>,
+        tools: &[ToolDefinition],
+        user_message: &str,
+    ) -> Vec<(AgentLoopResult, Vec<LoopPhase>)> {
+        let mut out = Vec::new();
+        for streaming in [false, true] {
+            let memory = openfang_memory::MemorySubstrate::open_in_memory(0.01).unwrap();
+            let mut session = openfang_memory::session::Session {
+                id: openfang_types::agent::SessionId::new(),
+                agent_id: openfang_types::agent::AgentId::new(),
+                messages: Vec::new(),
+                context_window_tokens: 0,
+                label: None,
+            };
+            let manifest = test_manifest();
+            let phases = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let phases_cb = phases.clone();
+            let on_phase: PhaseCallback = Arc::new(move |p| phases_cb.lock().unwrap().push(p));
+
+            let result = if streaming {
+                let (tx, mut rx) = mpsc::channel(64);
+                let drain = tokio::spawn(async move { while rx.recv().await.is_some() {} });
+                let r = run_agent_loop_streaming(
+                    &manifest,
+                    user_message,
+                    &mut session,
+                    &memory,
+                    make_driver(),
+                    tools,
+                    None,
+                    tx,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(&on_phase),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await;
+                drain.await.unwrap();
+                r
+            } else {
+                run_agent_loop(
+                    &manifest,
+                    user_message,
+                    &mut session,
+                    &memory,
+                    make_driver(),
+                    tools,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(&on_phase),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+            };
+            let phases = phases.lock().unwrap().clone();
+            out.push((result.expect("Loop should complete"), phases));
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn test_both_modes_retry_empty_first_response() {
+        let runs = run_both_modes(|| Arc::new(EmptyThenNormalDriver::new()), &[], "Hello").await;
+        for (result, _) in &runs {
+            assert_eq!(result.response, "Recovered after retry!");
+            assert_eq!(result.iterations, 2);
+            assert_eq!(result.total_usage.input_tokens, 25);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_both_modes_text_tool_call_recovery() {
+        let tools = vec![ToolDefinition {
+            name: "web_search".into(),
+            description: "Search the web".into(),
+            input_schema: serde_json::json!({}),
+        }];
+        let runs = run_both_modes(
+            || Arc::new(TextToolCallDriver::new()),
+            &tools,
+            "Search for rust async programming",
+        )
+        .await;
+        assert_eq!(runs[0].0.response, runs[1].0.response);
+        assert_eq!(runs[0].0.iterations, runs[1].0.iterations);
+    }
+
+    #[tokio::test]
+    async fn test_both_modes_report_mode_specific_phase() {
+        let runs = run_both_modes(|| Arc::new(NormalDriver), &[], "Say hello").await;
+        let (_, complete_phases) = &runs[0];
+        let (_, stream_phases) = &runs[1];
+        assert_eq!(complete_phases, &vec![LoopPhase::Thinking, LoopPhase::Done]);
+        assert_eq!(stream_phases, &vec![LoopPhase::Streaming, LoopPhase::Done]);
+    }
+
+    // --- Parallel tool execution ---
+
+    #[test]
+    fn test_parallel_runs_groups_consecutive_safe_calls() {
+        assert!(parallel_runs(&[]).is_empty());
+        assert_eq!(parallel_runs(&[true, true, true]), vec![0..3]);
+        assert_eq!(parallel_runs(&[false, false]), vec![0..1, 1..2]);
+        assert_eq!(
+            parallel_runs(&[true, true, false, true, false, false, true, true]),
+            vec![0..2, 2..3, 3..4, 4..5, 5..6, 6..8]
+        );
+    }
+
+    /// Mock driver whose first turn requests several tools at once
+    /// (read-only and side-effecting mixed), then finishes with text.
+    struct MultiToolDriver {
+        call_count: AtomicU32,
+    }
+
+    #[async_trait]
+    impl LlmDriver for MultiToolDriver {
+        async fn complete(
+            &self,
+            _request: CompletionRequest,
+        ) -> Result<CompletionResponse, LlmError> {
+            let call = self.call_count.fetch_add(1, Ordering::Relaxed);
+            if call == 0 {
+                let calls: Vec<ToolCall> = [
+                    ("t1", "file_read"),
+                    ("t2", "web_search"),
+                    ("t3", "file_write"),
+                    ("t4", "file_list"),
+                ]
+                .iter()
+                .map(|(id, name)| ToolCall {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    input: serde_json::json!({}),
+                })
+                .collect();
+                Ok(CompletionResponse {
+                    content: calls
+                        .iter()
+                        .map(|c| ContentBlock::ToolUse {
+                            id: c.id.clone(),
+                            name: c.name.clone(),
+                            input: c.input.clone(),
+                        })
+                        .collect(),
+                    stop_reason: StopReason::ToolUse,
+                    tool_calls: calls,
+                    usage: TokenUsage {
+                        input_tokens: 10,
+                        output_tokens: 5,
+                    },
+                })
+            } else {
+                Ok(CompletionResponse {
+                    content: vec![ContentBlock::Text {
+                        text: "All done.".to_string(),
+                    }],
+                    stop_reason: StopReason::EndTurn,
+                    tool_calls: vec![],
+                    usage: TokenUsage {
+                        input_tokens: 10,
+                        output_tokens: 3,
+                    },
+                })
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parallel_tool_results_preserve_order() {
+        let memory = openfang_memory::MemorySubstrate::open_in_memory(0.01).unwrap();
+        let agent_id = openfang_types::agent::AgentId::new();
+        let mut session = openfang_memory::session::Session {
+            id: openfang_types::agent::SessionId::new(),
+            agent_id,
+            messages: Vec::new(),
+            context_window_tokens: 0,
+            label: None,
+        };
+        let manifest = test_manifest();
+        let driver: Arc<dyn LlmDriver> = Arc::new(MultiToolDriver {
+            call_count: AtomicU32::new(0),
+        });
+
+        let result = run_agent_loop(
+            &manifest,
+            "Look around",
+            &mut session,
+            &memory,
+            driver,
+            &[], // no tools granted — every call is denied, which is fine here
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None, // on_phase
+            None, // media_engine
+            None, // tts_engine
+            None, // docker_config
+            None, // hooks
+            None, // context_window_tokens
+            None, // process_manager
+        )
+        .await
+        .expect("Loop should complete");
+        assert_eq!(result.response, "All done.");
+
+        let tool_result_ids: Vec<String> = session
+            .messages
+            .iter()
+            .filter_map(|m| match &m.content {
+                MessageContent::Blocks(blocks) => Some(blocks),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|b| match b {
+                ContentBlock::ToolResult { tool_use_id, .. } => Some(tool_use_id.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tool_result_ids, vec!["t1", "t2", "t3", "t4"]);
+    }
+
     // --- Streaming path: text-as-tool-call recovery ---
 
     #[tokio::test]
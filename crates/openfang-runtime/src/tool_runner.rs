@@ -89,6 +89,37 @@ pub fn current_agent_depth() -> u32 {
     AGENT_CALL_DEPTH.try_with(|d| d.get()).unwrap_or(0)
 }
 
+/// Built-in tools that only read state and may run concurrently with each other.
+///
+/// Anything that writes files, spawns processes, messages other agents or
+/// drives the shared browser session is deliberately excluded.
+const PARALLEL_SAFE_TOOLS: &[&str] = &[
+    "file_read",
+    "file_list",
+    "web_fetch",
+    "web_search",
+    "memory_recall",
+    "agent_list",
+    "agent_find",
+    "task_list",
+    "knowledge_query",
+    "schedule_list",
+    "cron_list",
+    "process_list",
+    "hand_list",
+    "hand_status",
+    "image_analyze",
+    "media_describe",
+    "media_transcribe",
+    "location_get",
+    "a2a_discover",
+];
+
+/// Whether a built-in tool is side-effect-free and safe to execute in parallel.
+pub fn is_parallel_safe_tool(tool_name: &str) -> bool {
+    PARALLEL_SAFE_TOOLS.contains(&tool_name)
+}
+
 /// Execute a tool by name with the given input, returning a ToolResult.
 ///
 /// The optional `kernel` handle enables inter-agent tools. If `None`,
@@ -2980,6 +3011,21 @@ async fn tool_canvas_present(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parallel_safe_tools_are_builtins() {
+        let names: Vec<String> = builtin_tool_definitions()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        for tool in PARALLEL_SAFE_TOOLS {
+            assert!(names.iter().any(|n| n == tool), "{tool} is not a built-in");
+        }
+        assert!(is_parallel_safe_tool("web_fetch"));
+        assert!(!is_parallel_safe_tool("shell_exec"));
+        assert!(!is_parallel_safe_tool("file_write"));
+        assert!(!is_parallel_safe_tool("browser_navigate"));
+    }
+
     #[test]
     fn test_builtin_tool_definitions() {
         let tools = builtin_tool_definitions();
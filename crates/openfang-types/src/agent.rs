@@ -257,6 +257,8 @@ pub struct ResourceQuota {
     pub max_cost_per_day_usd: f64,
     /// Maximum cost in USD per month (0.0 = unlimited).
     pub max_cost_per_month_usd: f64,
+    /// Maximum side-effect-free tool calls executed concurrently in one turn (1 = sequential).
+    pub max_parallel_tools: u32,
 }
 
 impl Default for ResourceQuota {
@@ -270,6 +272,7 @@ impl Default for ResourceQuota {
             max_cost_per_hour_usd: 1.0,
             max_cost_per_day_usd: 0.0,   // unlimited
             max_cost_per_month_usd: 0.0, // unlimited
+            max_parallel_tools: 4,
         }
     }
 }
@@ -410,6 +413,10 @@ pub struct FallbackModel {
 pub struct ToolConfig {
     /// Tool-specific configuration parameters.
     pub params: HashMap<String, serde_json::Value>,
+    /// Marks the tool as side-effect-free so it may run concurrently with
+    /// other parallel-safe calls from the same assistant turn.
+    #[serde(default)]
+    pub parallel_safe: bool,
 }
 
 /// Complete agent manifest — defines everything about an agent.